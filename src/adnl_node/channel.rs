@@ -35,6 +35,46 @@ impl AdnlChannel {
             std::cmp::Ordering::Greater => (reversed_secret, shared_secret),
         };
 
+        Self::from_secrets(local_id, peer_id, in_secret, out_secret)
+    }
+
+    /// Simultaneous-open variant of [`Self::new`] for NAT hole punching,
+    /// where both sides may have dialed at once and so can't rely on
+    /// `local_id.cmp(&peer_id)` alone to agree on roles.
+    pub fn with_nonces(
+        local_id: AdnlNodeIdShort,
+        peer_id: AdnlNodeIdShort,
+        local_private_key_part: &[u8; 32],
+        peer_public_key: &[u8; 32],
+        local_open_nonce: u64,
+        peer_open_nonce: u64,
+    ) -> Result<SimultaneousOpenOutcome> {
+        let role = match resolve_simultaneous_open_role(local_open_nonce, peer_open_nonce) {
+            NonceRole::Retry => return Ok(SimultaneousOpenOutcome::Retry),
+            role => role,
+        };
+
+        let shared_secret = compute_shared_secret(local_private_key_part, peer_public_key)?;
+        let mut reversed_secret = shared_secret;
+        reversed_secret.reverse();
+
+        let (in_secret, out_secret) = match role {
+            NonceRole::Initiator => (shared_secret, reversed_secret),
+            NonceRole::Responder => (reversed_secret, shared_secret),
+            NonceRole::Retry => unreachable!(),
+        };
+
+        Ok(SimultaneousOpenOutcome::Resolved(Self::from_secrets(
+            local_id, peer_id, in_secret, out_secret,
+        )?))
+    }
+
+    fn from_secrets(
+        local_id: AdnlNodeIdShort,
+        peer_id: AdnlNodeIdShort,
+        in_secret: [u8; 32],
+        out_secret: [u8; 32],
+    ) -> Result<Self> {
         Ok(Self {
             channel_out: ChannelSide::from_secret(in_secret)?,
             channel_in: ChannelSide::from_secret(out_secret)?,
@@ -119,6 +159,28 @@ impl ChannelSide {
     }
 }
 
+/// Outcome of [`AdnlChannel::with_nonces`]: either a resolved channel, or a
+/// tie that the caller must break by re-rolling its `open_nonce` and
+/// retrying the handshake.
+pub enum SimultaneousOpenOutcome {
+    Resolved(AdnlChannel),
+    Retry,
+}
+
+enum NonceRole {
+    Initiator,
+    Responder,
+    Retry,
+}
+
+fn resolve_simultaneous_open_role(local_open_nonce: u64, peer_open_nonce: u64) -> NonceRole {
+    match local_open_nonce.cmp(&peer_open_nonce) {
+        std::cmp::Ordering::Greater => NonceRole::Initiator,
+        std::cmp::Ordering::Less => NonceRole::Responder,
+        std::cmp::Ordering::Equal => NonceRole::Retry,
+    }
+}
+
 pub type AdnlChannelId = [u8; 32];
 
 fn compute_channel_id(secret: [u8; 32]) -> Result<AdnlChannelId> {
@@ -138,4 +200,42 @@ enum AdnlChannelError {
     ChannelMessageIsTooShort(usize),
     #[error("Invalid channel message checksum")]
     InvalidChannelMessageChecksum,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equal_nonces_retry() {
+        assert!(matches!(
+            resolve_simultaneous_open_role(42, 42),
+            NonceRole::Retry
+        ));
+    }
+
+    #[test]
+    fn larger_nonce_is_initiator() {
+        assert!(matches!(
+            resolve_simultaneous_open_role(10, 5),
+            NonceRole::Initiator
+        ));
+        assert!(matches!(
+            resolve_simultaneous_open_role(5, 10),
+            NonceRole::Responder
+        ));
+    }
+
+    #[test]
+    fn both_sides_agree_on_roles() {
+        let (a, b) = (7u64, 3u64);
+        assert!(matches!(
+            resolve_simultaneous_open_role(a, b),
+            NonceRole::Initiator
+        ));
+        assert!(matches!(
+            resolve_simultaneous_open_role(b, a),
+            NonceRole::Responder
+        ));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,57 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use anyhow::Result;
+use rand::Rng;
+
+mod channel;
+
+pub use channel::{AdnlChannel, AdnlChannelId, SimultaneousOpenOutcome};
+
+use crate::utils::*;
+
+// Socket handling, key storage and packet dispatch live outside this
+// checkout; these are placeholders just so channel setup has a home.
+pub struct AdnlNodeConfig;
+pub struct AdnlNode;
+
+impl AdnlNode {
+    /// Simultaneous-open mode for channel setup (NAT hole punching): drives
+    /// [`AdnlChannel::with_nonces`], re-rolling `open_nonce` on a tie.
+    /// `exchange_nonce` performs the actual channel-create round-trip,
+    /// which belongs to packet handling and isn't part of this checkout.
+    pub async fn setup_channel_simultaneous_open(
+        local_id: AdnlNodeIdShort,
+        peer_id: AdnlNodeIdShort,
+        local_private_key_part: &[u8; 32],
+        peer_public_key: &[u8; 32],
+        mut exchange_nonce: impl FnMut(u64) -> Pin<Box<dyn Future<Output = Result<u64>> + Send>>,
+    ) -> Result<AdnlChannel> {
+        const MAX_RETRIES: u32 = 8;
+
+        for _ in 0..MAX_RETRIES {
+            let local_open_nonce: u64 = rand::thread_rng().gen();
+            let peer_open_nonce = exchange_nonce(local_open_nonce).await?;
+
+            match AdnlChannel::with_nonces(
+                local_id,
+                peer_id,
+                local_private_key_part,
+                peer_public_key,
+                local_open_nonce,
+                peer_open_nonce,
+            )? {
+                SimultaneousOpenOutcome::Resolved(channel) => return Ok(channel),
+                SimultaneousOpenOutcome::Retry => continue,
+            }
+        }
+
+        Err(AdnlNodeError::SimultaneousOpenNegotiationFailed.into())
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+enum AdnlNodeError {
+    #[error("Simultaneous-open channel negotiation did not converge after max retries")]
+    SimultaneousOpenNegotiationFailed,
+}
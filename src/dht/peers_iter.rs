@@ -1,3 +1,9 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::net::IpAddr;
+
+use rand::Rng;
+
 use super::buckets::get_affinity;
 use super::node::Node;
 use super::storage::StorageKeyId;
@@ -90,3 +96,158 @@ impl PeersIter {
         }
     }
 }
+
+/// Number of live samples a [`SampledPeersIter`] holds at once.
+const DEFAULT_SAMPLE_SLOTS: usize = 32;
+
+/// Basalt-style uniform peer sampler, unbiased by key affinity unlike
+/// [`PeersIter`]. Each slot keeps the peer minimizing `hash(seed_i ||
+/// ip_bucket || peer_id)` among all peers seen so far.
+pub struct SampledPeersIter {
+    slots: Vec<Slot>,
+}
+
+struct Slot {
+    seed: u64,
+    holder: Option<(adnl::NodeIdShort, u64)>,
+}
+
+impl Slot {
+    fn with_random_seed() -> Self {
+        Self {
+            seed: rand::thread_rng().gen(),
+            holder: None,
+        }
+    }
+
+    fn reseed(&mut self) {
+        self.seed = rand::thread_rng().gen();
+        self.holder = None;
+    }
+
+    fn rank(&self, peer_id: &adnl::NodeIdShort, ip_bucket: u64) -> u64 {
+        rank_bytes(self.seed, ip_bucket, peer_id.as_slice())
+    }
+}
+
+fn rank_bytes(seed: u64, ip_bucket: u64, peer_id_bytes: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    ip_bucket.hash(&mut hasher);
+    peer_id_bytes.hash(&mut hasher);
+    hasher.finish()
+}
+
+impl SampledPeersIter {
+    pub fn new() -> Self {
+        Self::with_slot_count(DEFAULT_SAMPLE_SLOTS)
+    }
+
+    pub fn with_slot_count(slot_count: usize) -> Self {
+        Self {
+            slots: (0..slot_count.max(1))
+                .map(|_| Slot::with_random_seed())
+                .collect(),
+        }
+    }
+
+    /// Scans all known peers, updating each slot with the peer that now
+    /// minimizes its ranking hash, and re-draws any slot whose holder has
+    /// since gone bad.
+    pub fn fill(&mut self, dht: &Node) {
+        for slot in &mut self.slots {
+            if matches!(&slot.holder, Some((peer_id, _)) if dht.is_bad_peer(peer_id)) {
+                slot.holder = None;
+            }
+        }
+
+        let mut index = 0;
+        while let Some(peer_id) = dht.known_peers().get(index) {
+            index += 1;
+
+            if dht.is_bad_peer(&peer_id) {
+                continue;
+            }
+
+            let ip_bucket = dht
+                .peer_ip(&peer_id)
+                .map(ip_prefix_bucket)
+                .unwrap_or_default();
+
+            for slot in &mut self.slots {
+                let rank = slot.rank(&peer_id, ip_bucket);
+                let should_replace = match &slot.holder {
+                    Some((_, current_rank)) => rank < *current_rank,
+                    None => true,
+                };
+                if should_replace {
+                    slot.holder = Some((peer_id, rank));
+                }
+            }
+        }
+    }
+
+    /// Re-rolls every slot's seed and forgets its holder, so the next
+    /// [`Self::fill`] re-draws a fresh sample.
+    pub fn rotate_seeds(&mut self) {
+        for slot in &mut self.slots {
+            slot.reseed();
+        }
+    }
+
+    /// Current slot holders: an approximately uniform sample of live peers.
+    pub fn sample(&self) -> Vec<adnl::NodeIdShort> {
+        self.slots
+            .iter()
+            .filter_map(|slot| slot.holder.as_ref().map(|(peer_id, _)| *peer_id))
+            .collect()
+    }
+}
+
+impl Default for SampledPeersIter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collapses an IP address to its routing-prefix bucket (/24 for IPv4, /48
+/// for IPv6), bounding how many slots a single attacker subnet can dominate.
+fn ip_prefix_bucket(ip: IpAddr) -> u64 {
+    match ip {
+        IpAddr::V4(v4) => (u32::from(v4) & 0xFFFFFF00) as u64,
+        IpAddr::V6(v6) => {
+            let segments = v6.segments();
+            ((segments[0] as u64) << 32) | ((segments[1] as u64) << 16) | (segments[2] as u64)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ipv4_buckets_collapse_the_host_octet() {
+        let a = ip_prefix_bucket(IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 1)));
+        let b = ip_prefix_bucket(IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 0, 254)));
+        assert_eq!(a, b);
+
+        let c = ip_prefix_bucket(IpAddr::V4(std::net::Ipv4Addr::new(10, 0, 1, 1)));
+        assert_ne!(a, c);
+    }
+
+    #[test]
+    fn rank_bytes_is_deterministic_for_same_inputs() {
+        assert_eq!(rank_bytes(123, 0, b"peer-a"), rank_bytes(123, 0, b"peer-a"));
+    }
+
+    #[test]
+    fn rank_bytes_depends_on_ip_bucket() {
+        assert_ne!(rank_bytes(123, 0, b"peer-a"), rank_bytes(123, 1, b"peer-a"));
+    }
+
+    #[test]
+    fn rank_bytes_depends_on_peer_id() {
+        assert_ne!(rank_bytes(123, 0, b"peer-a"), rank_bytes(123, 0, b"peer-b"));
+    }
+}
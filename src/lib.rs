@@ -3,7 +3,7 @@
 pub use adnl_node::{AdnlNode, AdnlNodeConfig};
 pub use dht_node::{DhtNode, ExternalDhtIterator};
 pub use overlay_node::OverlayNode;
-pub use rldp_node::RldpNode;
+pub use rldp_node::{RldpNode, RldpQueryPriority};
 pub use subscriber::{
     OverlaySubscriber, QueryBundleConsumingResult, QueryConsumingResult, Subscriber,
 };
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use anyhow::Result;
+use dashmap::DashMap;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
+
+use crate::rldp_node::{RldpNode, RldpQueryPriority};
+use crate::utils::*;
+
+/// Fan-out width for the first wave of a parallel fetch, before any
+/// escalation to additional candidates.
+const INITIAL_FANOUT: usize = 2;
+
+pub struct OverlayNode {
+    rldp: Arc<RldpNode>,
+    peer_stats: DashMap<AdnlNodeIdShort, PeerStats>,
+}
+
+impl OverlayNode {
+    pub fn with_rldp_node(rldp: Arc<RldpNode>) -> Arc<Self> {
+        Arc::new(Self {
+            rldp,
+            peer_stats: Default::default(),
+        })
+    }
+
+    /// Parallel, fastest-peer-wins RLDP fetch across `candidates`.
+    ///
+    /// Sends the same query to a small initial fan-out of peers at once,
+    /// ranked by whatever latency/success history we have for them, and
+    /// returns as soon as any of them produces a valid answer. Losing
+    /// queries are dropped, which cancels their `RldpNode::query` futures
+    /// mid-flight; `RldpNode` removes the abandoned transfer's entries and
+    /// aborts its receive task on drop, but a cancelled query does not reach
+    /// its per-peer `end_query` call. On timeout or error the fetch escalates to additional
+    /// candidates from the list instead of retrying the peer that just
+    /// failed, turning a single-peer, serial `RldpNode::query` into a
+    /// resilient swarm download.
+    ///
+    /// Splitting very large payloads into disjoint ranges fetched from
+    /// different peers is not implemented here; this only races whole
+    /// answers.
+    pub async fn parallel_fetch(
+        &self,
+        local_id: &AdnlNodeIdShort,
+        candidates: &[AdnlNodeIdShort],
+        data: &[u8],
+        max_answer_size: Option<i64>,
+    ) -> Result<Vec<u8>> {
+        if candidates.is_empty() {
+            return Err(OverlayNodeError::NoCandidates.into());
+        }
+
+        let mut remaining = self.rank_candidates(candidates);
+        let mut in_flight = FuturesUnordered::new();
+
+        for _ in 0..INITIAL_FANOUT.min(remaining.len()) {
+            let peer_id = remaining.pop().unwrap();
+            in_flight.push(self.query_peer(*local_id, peer_id, data, max_answer_size));
+        }
+
+        while let Some((peer_id, result)) = in_flight.next().await {
+            match result {
+                Ok((Some(answer), roundtrip)) => {
+                    self.record_success(&peer_id, roundtrip);
+                    return Ok(answer);
+                }
+                Ok((None, roundtrip)) => {
+                    self.record_timeout(&peer_id, roundtrip);
+                }
+                Err(e) => {
+                    log::warn!("parallel fetch: peer query failed: {}", e);
+                    self.record_failure(&peer_id);
+                }
+            }
+
+            if let Some(peer_id) = remaining.pop() {
+                in_flight.push(self.query_peer(*local_id, peer_id, data, max_answer_size));
+            }
+        }
+
+        Err(OverlayNodeError::AllCandidatesFailed.into())
+    }
+
+    async fn query_peer(
+        &self,
+        local_id: AdnlNodeIdShort,
+        peer_id: AdnlNodeIdShort,
+        data: &[u8],
+        max_answer_size: Option<i64>,
+    ) -> (AdnlNodeIdShort, Result<(Option<Vec<u8>>, u64)>) {
+        let roundtrip = self
+            .peer_stats
+            .get(&peer_id)
+            .map(|stats| stats.last_roundtrip);
+
+        // Parallel fetches are bulk, best-effort swarm downloads, not
+        // latency-sensitive control traffic, so they get the lowest share
+        // of each peer's send budget.
+        let result = self
+            .rldp
+            .query(
+                &local_id,
+                &peer_id,
+                data,
+                max_answer_size,
+                roundtrip,
+                RldpQueryPriority::Low,
+            )
+            .await;
+
+        (peer_id, result)
+    }
+
+    /// Orders `candidates` worst-first, so the fastest/most reliable peer
+    /// (by accumulated stats) is popped off the back first. Peers we've
+    /// seen fail more often than succeed are tried last; unseen peers rank
+    /// ahead of known-bad ones but behind known-good ones.
+    fn rank_candidates(&self, candidates: &[AdnlNodeIdShort]) -> Vec<AdnlNodeIdShort> {
+        let mut ranked = candidates.to_vec();
+        ranked.sort_by_key(|peer_id| rank_key(self.peer_stats.get(peer_id).map(|stats| *stats)));
+        ranked.reverse();
+        ranked
+    }
+
+    fn record_success(&self, peer_id: &AdnlNodeIdShort, roundtrip: u64) {
+        let mut stats = self.peer_stats.entry(*peer_id).or_default();
+        stats.successes += 1;
+        stats.last_roundtrip = roundtrip;
+    }
+
+    fn record_timeout(&self, peer_id: &AdnlNodeIdShort, roundtrip: u64) {
+        let mut stats = self.peer_stats.entry(*peer_id).or_default();
+        stats.failures += 1;
+        stats.last_roundtrip = roundtrip;
+    }
+
+    fn record_failure(&self, peer_id: &AdnlNodeIdShort) {
+        self.peer_stats.entry(*peer_id).or_default().failures += 1;
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct PeerStats {
+    successes: u32,
+    failures: u32,
+    last_roundtrip: u64,
+}
+
+/// Sort key for [`OverlayNode::rank_candidates`]: lower sorts better (ends up
+/// at the back after the ascending sort is reversed, so it's tried first).
+fn rank_key(stats: Option<PeerStats>) -> u64 {
+    match stats {
+        Some(stats) if stats.failures > stats.successes => u64::MAX,
+        Some(stats) => stats.last_roundtrip,
+        None => u64::MAX / 2,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unseen_peer_ranks_between_good_and_bad() {
+        let good = rank_key(Some(PeerStats {
+            successes: 1,
+            failures: 0,
+            last_roundtrip: 100,
+        }));
+        let bad = rank_key(Some(PeerStats {
+            successes: 0,
+            failures: 1,
+            last_roundtrip: 100,
+        }));
+        let unseen = rank_key(None);
+        assert!(good < unseen);
+        assert!(unseen < bad);
+    }
+
+    #[test]
+    fn faster_roundtrip_ranks_better() {
+        let fast = rank_key(Some(PeerStats {
+            successes: 1,
+            failures: 0,
+            last_roundtrip: 50,
+        }));
+        let slow = rank_key(Some(PeerStats {
+            successes: 1,
+            failures: 0,
+            last_roundtrip: 500,
+        }));
+        assert!(fast < slow);
+    }
+}
+
+#[derive(thiserror::Error, Debug)]
+enum OverlayNodeError {
+    #[error("No candidate peers provided")]
+    NoCandidates,
+    #[error("All candidate peers failed")]
+    AllCandidatesFailed,
+}
@@ -26,6 +26,7 @@ pub struct RldpNode {
     subscribers: Arc<Vec<Arc<dyn Subscriber>>>,
     peers: DashMap<AdnlNodeIdShort, Arc<RldpPeer>>,
     transfers: Arc<DashMap<TransferId, RldpTransfer>>,
+    schedulers: DashMap<AdnlNodeIdShort, Arc<PeerScheduler>>,
 }
 
 impl RldpNode {
@@ -35,6 +36,7 @@ impl RldpNode {
             subscribers: Arc::new(subscribers),
             peers: Default::default(),
             transfers: Arc::new(Default::default()),
+            schedulers: Default::default(),
         })
     }
 
@@ -45,9 +47,19 @@ impl RldpNode {
         data: &[u8],
         max_answer_size: Option<i64>,
         roundtrip: Option<u64>,
+        priority: RldpQueryPriority,
     ) -> Result<(Option<Vec<u8>>, u64)> {
         use dashmap::mapref::entry::Entry;
 
+        // Messages this small are sent inline: they skip the per-peer
+        // `PeerScheduler` wait entirely instead of just being promoted to a
+        // higher share of it, since a single chunk can't meaningfully queue
+        // behind anything. They still go through `OutgoingTransfer`/RaptorQ
+        // framing like any other transfer; fully bypassing that would need
+        // a second wire encoding this checkout's transfer types don't expose.
+        let inline = data.len() <= INLINE_MESSAGE_THRESHOLD;
+        let priority = if inline { RldpQueryPriority::High } else { priority };
+
         let query_id: QueryId = rand::thread_rng().gen();
         let message = ton::rldp::message::Query {
             query_id: ton::int256(query_id),
@@ -62,6 +74,10 @@ impl RldpNode {
             Entry::Occupied(entry) => entry.get().clone(),
             Entry::Vacant(entry) => entry.insert(Default::default()).value().clone(),
         };
+        let scheduler = match self.schedulers.entry(*peer_id) {
+            Entry::Occupied(entry) => entry.get().clone(),
+            Entry::Vacant(entry) => entry.insert(PeerScheduler::new()).value().clone(),
+        };
 
         peer.begin_query().await;
 
@@ -88,6 +104,9 @@ impl RldpNode {
             peer_id: *peer_id,
             transfer: outgoing_transfer,
             transfer_id: outgoing_transfer_id,
+            scheduler,
+            priority,
+            inline,
         };
 
         let incoming_context = IncomingContext {
@@ -100,23 +119,14 @@ impl RldpNode {
         };
 
         let result = self
-            .query_transfer_loop(outgoing_context, incoming_context, roundtrip)
+            .query_transfer_loop(
+                outgoing_context,
+                incoming_context,
+                incoming_transfer_id,
+                roundtrip,
+            )
             .await;
 
-        if result.is_err() {
-            self.transfers
-                .insert(outgoing_transfer_id, RldpTransfer::Done);
-        }
-        self.transfers
-            .insert(incoming_transfer_id, RldpTransfer::Done);
-
-        let transfers = self.transfers.clone();
-        tokio::spawn(async move {
-            tokio::time::sleep(Duration::from_millis(MAX_TIMEOUT * 2)).await;
-            transfers.remove(&outgoing_transfer_id);
-            transfers.remove(&incoming_transfer_id);
-        });
-
         peer.end_query().await;
 
         match result? {
@@ -141,6 +151,7 @@ impl RldpNode {
         &self,
         outgoing_context: OutgoingContext<'_>,
         mut incoming_context: IncomingContext,
+        incoming_transfer_id: TransferId,
         roundtrip: Option<u64>,
     ) -> Result<(Option<Vec<u8>>, u64)> {
         let barrier: Arc<Mutex<Option<IncomingTransfer>>> = Arc::new(Mutex::new(None));
@@ -149,7 +160,7 @@ impl RldpNode {
         let outgoing_transfer_state = outgoing_context.transfer.state().clone();
         let outgoing_transfer_id = outgoing_context.transfer_id;
 
-        tokio::spawn({
+        let receive_task = tokio::spawn({
             let barrier = barrier.clone();
             async move {
                 receive_loop(&mut incoming_context, Some(outgoing_transfer_state)).await;
@@ -157,13 +168,33 @@ impl RldpNode {
             }
         });
 
-        let (ok, mut roundtrip) = send_loop(outgoing_context, roundtrip).await?;
+        let guard = TransferGuard {
+            transfers: self.transfers.clone(),
+            outgoing_transfer_id,
+            incoming_transfer_id,
+            receive_task,
+        };
 
-        let mut timeout = calc_timeout(Some(roundtrip));
+        let (ok, mut roundtrip) = match send_loop(outgoing_context, roundtrip).await {
+            Ok(v) => v,
+            Err(e) => {
+                // A real send error, not a dropped/cancelled future: still
+                // goes through the guard's normal delayed-removal cleanup
+                // rather than the immediate removal `Drop` does on
+                // cancellation, so this path's timing is unchanged from
+                // before `TransferGuard` existed.
+                guard.disarm();
+                return Err(e);
+            }
+        };
+
+        let mut rtt = RttEstimator::with_initial_roundtrip(roundtrip);
+        let mut timeout = rtt.timeout();
         self.transfers
             .insert(outgoing_transfer_id, RldpTransfer::Done);
 
         if !ok {
+            guard.disarm();
             return Ok((None, roundtrip));
         }
 
@@ -173,7 +204,8 @@ impl RldpNode {
             tokio::time::sleep(Duration::from_millis(TRANSFER_LOOP_INTERVAL)).await;
             let new_updates = incoming_transfer_state.updates();
             if new_updates > updates {
-                timeout = update_roundtrip(&mut roundtrip, &start);
+                timeout = rtt.update(start.elapsed().as_millis() as u64);
+                roundtrip = rtt.smoothed();
                 updates = new_updates;
                 start = Instant::now();
             } else if is_timed_out(&start, timeout, updates) {
@@ -181,10 +213,12 @@ impl RldpNode {
             }
 
             if let Some(reply) = barrier.lock().take() {
-                update_roundtrip(&mut roundtrip, &start);
-                return Ok((Some(reply.into_data()), roundtrip));
+                rtt.update(start.elapsed().as_millis() as u64);
+                guard.disarm();
+                return Ok((Some(reply.into_data()), rtt.smoothed()));
             }
         }
+        guard.disarm();
         Ok((None, roundtrip))
     }
 
@@ -204,12 +238,54 @@ enum RldpTransfer {
     Done,
 }
 
+/// Cleans up a query's transfer-map entries and receive task immediately if
+/// `query_transfer_loop` is dropped before reaching a return point (e.g. the
+/// future being raced away by a caller like `OverlayNode::parallel_fetch`).
+/// Every return point calls [`Self::disarm`] instead, which marks both
+/// transfers done, removes them after `MAX_TIMEOUT * 2` and skips `Drop`.
+struct TransferGuard {
+    transfers: Arc<DashMap<TransferId, RldpTransfer>>,
+    outgoing_transfer_id: TransferId,
+    incoming_transfer_id: TransferId,
+    receive_task: tokio::task::JoinHandle<()>,
+}
+
+impl TransferGuard {
+    fn disarm(self) {
+        let transfers = self.transfers.clone();
+        let outgoing_transfer_id = self.outgoing_transfer_id;
+        let incoming_transfer_id = self.incoming_transfer_id;
+
+        transfers.insert(outgoing_transfer_id, RldpTransfer::Done);
+        transfers.insert(incoming_transfer_id, RldpTransfer::Done);
+
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(MAX_TIMEOUT * 2)).await;
+            transfers.remove(&outgoing_transfer_id);
+            transfers.remove(&incoming_transfer_id);
+        });
+
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for TransferGuard {
+    fn drop(&mut self) {
+        self.receive_task.abort();
+        self.transfers.remove(&self.outgoing_transfer_id);
+        self.transfers.remove(&self.incoming_transfer_id);
+    }
+}
+
 struct OutgoingContext<'a> {
     adnl: Arc<AdnlNode>,
     local_id: AdnlNodeIdShort,
     peer_id: AdnlNodeIdShort,
     transfer: OutgoingTransfer<'a>,
     transfer_id: TransferId,
+    scheduler: Arc<PeerScheduler>,
+    priority: RldpQueryPriority,
+    inline: bool,
 }
 
 struct IncomingContext {
@@ -264,19 +340,32 @@ async fn send_loop(
     mut outgoing_context: OutgoingContext<'_>,
     roundtrip: Option<u64>,
 ) -> Result<(bool, u64)> {
-    const MAX_TRANSFER_WAVE: u32 = 10;
-
-    let mut timeout = calc_timeout(roundtrip);
-    let mut roundtrip = roundtrip.unwrap_or_default();
-
-    while let Some(transfer_wave) = outgoing_context.transfer.start_next_part()? {
-        let transfer_wave = std::cmp::min(transfer_wave, MAX_TRANSFER_WAVE);
-
+    // Congestion window, in RaptorQ symbols.
+    const MIN_CWND: u32 = 4;
+    const MAX_CWND: u32 = 64;
+    // Halve `cwnd` on timeout instead of aborting; give up after this many
+    // consecutive halvings.
+    const MAX_CONSECUTIVE_DECREASES: u32 = 5;
+
+    let mut rtt = RttEstimator::with_initial_roundtrip(roundtrip.unwrap_or_default());
+    let mut timeout = rtt.timeout();
+    let mut cwnd = MIN_CWND;
+    let mut consecutive_decreases = 0;
+
+    while let Some(symbols_remaining) = outgoing_context.transfer.start_next_part()? {
         let part = outgoing_context.transfer.state().part();
         let mut start = Instant::now();
         let mut incoming_seqno = 0;
         'part: loop {
+            let transfer_wave = std::cmp::min(cwnd, symbols_remaining);
             for _ in 0..transfer_wave {
+                if !outgoing_context.inline {
+                    outgoing_context
+                        .scheduler
+                        .acquire_chunk_budget(outgoing_context.priority)
+                        .await;
+                }
+
                 outgoing_context.adnl.send_custom_message(
                     &outgoing_context.local_id,
                     &outgoing_context.peer_id,
@@ -296,36 +385,207 @@ async fn send_loop(
 
             let new_incoming_seqno = outgoing_context.transfer.state().seqno_in();
             if new_incoming_seqno > incoming_seqno {
-                timeout = update_roundtrip(&mut roundtrip, &start);
+                timeout = rtt.update(start.elapsed().as_millis() as u64);
                 incoming_seqno = new_incoming_seqno;
                 start = Instant::now();
+
+                // Additive increase.
+                cwnd = std::cmp::min(cwnd + 1, MAX_CWND);
+                consecutive_decreases = 0;
             } else if is_timed_out(&start, timeout, incoming_seqno) {
-                return Ok((false, std::cmp::min(roundtrip * 2, MAX_TIMEOUT)));
+                consecutive_decreases += 1;
+                if consecutive_decreases > MAX_CONSECUTIVE_DECREASES {
+                    return Ok((false, std::cmp::min(rtt.smoothed() * 2, MAX_TIMEOUT)));
+                }
+
+                // Multiplicative decrease.
+                cwnd = std::cmp::max(cwnd / 2, MIN_CWND);
+                start = Instant::now();
             }
         }
-        timeout = update_roundtrip(&mut roundtrip, &start);
+        timeout = rtt.update(start.elapsed().as_millis() as u64);
     }
 
-    Ok((true, roundtrip))
+    Ok((true, rtt.smoothed()))
 }
 
-fn update_roundtrip(roundtrip: &mut u64, time: &Instant) -> u64 {
-    *roundtrip = if *roundtrip == 0 {
-        time.elapsed().as_millis() as u64
-    } else {
-        *roundtrip + (time.elapsed().as_millis() as u64) / 2
-    };
-    calc_timeout(Some(*roundtrip))
+/// Jacobson/Karels RTT estimator backing the RLDP retransmit timeout.
+struct RttEstimator {
+    srtt: u64,
+    rttvar: u64,
 }
 
-fn calc_timeout(roundtrip: Option<u64>) -> u64 {
-    std::cmp::max(roundtrip.unwrap_or(MAX_TIMEOUT), MIN_TIMEOUT)
+impl RttEstimator {
+    fn with_initial_roundtrip(roundtrip: u64) -> Self {
+        if roundtrip == 0 {
+            Self { srtt: 0, rttvar: 0 }
+        } else {
+            Self {
+                srtt: roundtrip,
+                rttvar: roundtrip / 2,
+            }
+        }
+    }
+
+    fn smoothed(&self) -> u64 {
+        self.srtt
+    }
+
+    fn update(&mut self, sample: u64) -> u64 {
+        if self.srtt == 0 {
+            self.srtt = sample;
+            self.rttvar = sample / 2;
+        } else {
+            let delta = (self.srtt as i64 - sample as i64).unsigned_abs();
+            self.rttvar = (3 * self.rttvar + delta) / 4;
+            self.srtt = (7 * self.srtt + sample) / 8;
+        }
+        self.timeout()
+    }
+
+    fn timeout(&self) -> u64 {
+        // No sample yet: stay conservative rather than assuming a fast link.
+        if self.srtt == 0 {
+            return MAX_TIMEOUT;
+        }
+        (self.srtt + 4 * self.rttvar).clamp(MIN_TIMEOUT, MAX_TIMEOUT)
+    }
+}
+
+#[cfg(test)]
+mod rtt_estimator_tests {
+    use super::*;
+
+    #[test]
+    fn unseeded_timeout_is_conservative() {
+        let rtt = RttEstimator::with_initial_roundtrip(0);
+        assert_eq!(rtt.timeout(), MAX_TIMEOUT);
+    }
+
+    #[test]
+    fn smoothed_tracks_repeated_samples() {
+        let mut rtt = RttEstimator::with_initial_roundtrip(0);
+        rtt.update(200);
+        rtt.update(200);
+        assert_eq!(rtt.smoothed(), 200);
+    }
+
+    #[test]
+    fn seeded_timeout_does_not_reset_to_max() {
+        let rtt = RttEstimator::with_initial_roundtrip(2000);
+        assert!(rtt.timeout() < MAX_TIMEOUT);
+    }
 }
 
 fn is_timed_out(time: &Instant, timeout: u64, updates: u32) -> bool {
     time.elapsed().as_millis() as u64 > timeout + timeout * (updates as u64) / 100
 }
 
+/// Relative priority of an RLDP query, used to weight how a peer's shared
+/// [`PeerScheduler`] interleaves chunk emission across concurrently active
+/// transfers to that peer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RldpQueryPriority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Default for RldpQueryPriority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+impl RldpQueryPriority {
+    /// Higher weight costs fewer credits per chunk (`MAX_WEIGHT / weight()`).
+    fn weight(self) -> u32 {
+        match self {
+            RldpQueryPriority::Low => 1,
+            RldpQueryPriority::Normal => 3,
+            RldpQueryPriority::High => 8,
+        }
+    }
+}
+
+/// Queries this small skip the [`PeerScheduler`] wait entirely (see
+/// `RldpNode::query`'s `inline` flag) rather than add latency for no benefit.
+const INLINE_MESSAGE_THRESHOLD: usize = 256; // Bytes
+
+/// Per-peer chunk emission scheduler, so a bulk transfer can't starve a
+/// latency-sensitive query to the same peer. Draws from a shared,
+/// periodically refilled credit pool weighted by [`RldpQueryPriority`].
+struct PeerScheduler {
+    credits: tokio::sync::Semaphore,
+}
+
+impl PeerScheduler {
+    /// Credits granted to a peer's transfers every [`TRANSFER_LOOP_INTERVAL`].
+    const CREDITS_PER_TICK: usize = 24;
+    const MAX_WEIGHT: u32 = 8; // RldpQueryPriority::High::weight()
+
+    fn new() -> Arc<Self> {
+        let scheduler = Arc::new(Self {
+            credits: tokio::sync::Semaphore::new(Self::CREDITS_PER_TICK),
+        });
+
+        tokio::spawn({
+            let scheduler = Arc::downgrade(&scheduler);
+            async move {
+                let mut interval =
+                    tokio::time::interval(Duration::from_millis(TRANSFER_LOOP_INTERVAL));
+                loop {
+                    interval.tick().await;
+                    match scheduler.upgrade() {
+                        Some(scheduler) => scheduler.credits.add_permits(Self::CREDITS_PER_TICK),
+                        None => break,
+                    }
+                }
+            }
+        });
+
+        scheduler
+    }
+
+    /// Waits for this priority's fair share of the shared send budget
+    /// before the caller is allowed to emit its next chunk.
+    async fn acquire_chunk_budget(&self, priority: RldpQueryPriority) {
+        let cost = Self::chunk_cost(priority);
+        if let Ok(permit) = self.credits.acquire_many(cost).await {
+            permit.forget();
+        }
+    }
+
+    fn chunk_cost(priority: RldpQueryPriority) -> u32 {
+        std::cmp::max(Self::MAX_WEIGHT / priority.weight(), 1)
+    }
+}
+
+#[cfg(test)]
+mod scheduler_tests {
+    use super::*;
+
+    #[test]
+    fn higher_priority_costs_fewer_credits() {
+        let low = PeerScheduler::chunk_cost(RldpQueryPriority::Low);
+        let normal = PeerScheduler::chunk_cost(RldpQueryPriority::Normal);
+        let high = PeerScheduler::chunk_cost(RldpQueryPriority::High);
+        assert!(low >= normal);
+        assert!(normal >= high);
+    }
+
+    #[test]
+    fn chunk_cost_is_never_zero() {
+        for priority in [
+            RldpQueryPriority::Low,
+            RldpQueryPriority::Normal,
+            RldpQueryPriority::High,
+        ] {
+            assert!(PeerScheduler::chunk_cost(priority) >= 1);
+        }
+    }
+}
+
 const MIN_TIMEOUT: u64 = 500;
 const MAX_TIMEOUT: u64 = 10000; // Milliseconds
 const TRANSFER_LOOP_INTERVAL: u64 = 10; // Milliseconds